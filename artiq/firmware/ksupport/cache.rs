@@ -0,0 +1,51 @@
+//! Kernel-side entry points for the byte-typed cache, packing/unpacking
+//! the Python types that don't fit the i32 cache (`f64`, `i64`) so their
+//! element type round-trips instead of needing to be hand-packed into
+//! i32s by the caller.
+
+use kernel_proto::Message::*;
+use comms::{send, recv};
+
+pub extern fn cache_get_f64(key: &str) -> f64 {
+    send(&CacheGetBytesRequest { key });
+    recv(|reply| match *reply {
+        CacheGetBytesReply { value } if value.len() == 8 => {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(value);
+            f64::from_le_bytes(bytes)
+        }
+        CacheGetBytesReply { .. } => 0.0,
+        _ => panic!("unexpected reply to CacheGetBytesRequest")
+    })
+}
+
+pub extern fn cache_put_f64(key: &str, value: f64) -> bool {
+    let bytes = value.to_le_bytes();
+    send(&CachePutBytesRequest { key, value: &bytes });
+    recv(|reply| match *reply {
+        CachePutBytesReply { succeeded } => succeeded,
+        _ => panic!("unexpected reply to CachePutBytesRequest")
+    })
+}
+
+pub extern fn cache_get_i64(key: &str) -> i64 {
+    send(&CacheGetBytesRequest { key });
+    recv(|reply| match *reply {
+        CacheGetBytesReply { value } if value.len() == 8 => {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(value);
+            i64::from_le_bytes(bytes)
+        }
+        CacheGetBytesReply { .. } => 0,
+        _ => panic!("unexpected reply to CacheGetBytesRequest")
+    })
+}
+
+pub extern fn cache_put_i64(key: &str, value: i64) -> bool {
+    let bytes = value.to_le_bytes();
+    send(&CachePutBytesRequest { key, value: &bytes });
+    recv(|reply| match *reply {
+        CachePutBytesReply { succeeded } => succeeded,
+        _ => panic!("unexpected reply to CachePutBytesRequest")
+    })
+}
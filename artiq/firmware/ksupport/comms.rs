@@ -0,0 +1,19 @@
+//! Thin kernel-side transport used by the SPI/DMA/cache/RPC entry points
+//! to exchange `Message`s with the comms CPU over the mailbox, the same
+//! mechanism the existing I2C entry points ride on.
+
+use kernel_proto::Message;
+
+extern "C" {
+    fn mailbox_send(ptr: *const ());
+    fn mailbox_receive() -> *const ();
+}
+
+pub fn send(message: &Message) {
+    unsafe { mailbox_send(message as *const Message as *const ()) }
+}
+
+pub fn recv<R, F: FnOnce(&Message) -> R>(f: F) -> R {
+    let reply = unsafe { mailbox_receive() } as *const Message;
+    f(unsafe { &*reply })
+}
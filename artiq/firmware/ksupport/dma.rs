@@ -0,0 +1,40 @@
+//! Kernel-side entry points for recording an RTIO pulse sequence into a
+//! named trace on the comms CPU and replaying it later, so repetitive
+//! sequences don't have to be re-emitted word-by-word over the mailbox.
+
+use kernel_proto::Message::*;
+use comms::{send, recv};
+
+#[no_mangle]
+pub extern fn dma_record_start(name: &str) {
+    send(&DmaRecordStart(name));
+}
+
+#[no_mangle]
+pub extern fn dma_record_append(data: &[u8]) {
+    send(&DmaRecordAppend(data));
+}
+
+#[no_mangle]
+pub extern fn dma_record_stop(duration: u64) {
+    send(&DmaRecordStop { duration });
+}
+
+#[no_mangle]
+pub extern fn dma_erase(name: &str) {
+    send(&DmaEraseRequest { name });
+}
+
+/// Fetches the persistent buffer pointer and duration for a previously
+/// recorded trace, for handing to the DMA playback engine.
+#[no_mangle]
+pub extern fn dma_retrieve(name: &str) -> (*const u8, usize, u64) {
+    send(&DmaRetrieveRequest { name });
+    recv(|reply| match *reply {
+        DmaRetrieveReply { trace: Some(trace), duration } =>
+            (trace.as_ptr(), trace.len(), duration),
+        DmaRetrieveReply { trace: None, .. } =>
+            panic!("no DMA trace named {:?}", name),
+        _ => panic!("unexpected reply to DmaRetrieveRequest")
+    })
+}
@@ -0,0 +1,38 @@
+//! Kernel-side entry points for tracking in-flight async RPCs, so a
+//! kernel tearing down can cancel them or wait for the queue to drain
+//! instead of losing data or outliving its own `RunFinished`.
+
+use kernel_proto::Message::*;
+use comms::{send, recv};
+
+/// Fires an async RPC and returns the correlation id the comms CPU
+/// assigned it.
+#[no_mangle]
+pub extern fn rpc_send_async(service: u32, tag: &[u8], data: *const *const ()) -> u32 {
+    send(&RpcSend { async: true, service: service, tag: tag, data: data });
+    recv(|reply| match *reply {
+        RpcSendReply { id } => id,
+        _ => panic!("unexpected reply to async RpcSend")
+    })
+}
+
+#[no_mangle]
+pub extern fn rpc_cancel(id: u32) {
+    send(&RpcCancelRequest { id });
+}
+
+/// Blocks until every async RPC queued so far has completed or been
+/// cancelled.
+#[no_mangle]
+pub extern fn rpc_async_drain() {
+    loop {
+        send(&RpcAsyncDrainRequest);
+        let pending = recv(|reply| match *reply {
+            RpcAsyncDrainReply { pending } => pending,
+            _ => panic!("unexpected reply to RpcAsyncDrainRequest")
+        });
+        if pending == 0 {
+            break
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Kernel-side entry points for driving SPI-connected peripherals,
+//! mirroring the I2C entry points: each call sends a request over the
+//! mailbox and blocks for the matching reply.
+
+use kernel_proto::Message::*;
+use comms::{send, recv};
+
+#[no_mangle]
+pub extern fn spi_set_config(busno: i32, flags: i32, length: i32, div: i32, cs: i32) {
+    send(&SpiSetConfigRequest {
+        busno:  busno as u8,
+        flags:  flags as u8,
+        length: length as u8,
+        div:    div as u8,
+        cs:     cs as u8,
+    });
+    recv(|reply| match *reply {
+        SpiSetConfigReply { succeeded: true } => (),
+        SpiSetConfigReply { succeeded: false } =>
+            panic!("failed to set SPI configuration on bus {}", busno),
+        _ => panic!("unexpected reply to SpiSetConfigRequest")
+    })
+}
+
+#[no_mangle]
+pub extern fn spi_write(busno: i32, data: i32) {
+    send(&SpiWriteRequest { busno: busno as u8, data: data as u32 });
+    recv(|reply| match *reply {
+        SpiWriteReply { succeeded: true } => (),
+        SpiWriteReply { succeeded: false } =>
+            panic!("SPI write failed on bus {}", busno),
+        _ => panic!("unexpected reply to SpiWriteRequest")
+    })
+}
+
+#[no_mangle]
+pub extern fn spi_read(busno: i32) -> i32 {
+    send(&SpiReadRequest { busno: busno as u8 });
+    recv(|reply| match *reply {
+        SpiReadReply { data } => data as i32,
+        _ => panic!("unexpected reply to SpiReadRequest")
+    })
+}
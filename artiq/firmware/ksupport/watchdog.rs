@@ -0,0 +1,16 @@
+//! Kernel-side entry point for inspecting an armed watchdog's remaining
+//! slack, so a kernel can schedule around an impending expiry instead of
+//! only finding out via an abrupt `RunAborted`.
+
+use kernel_proto::Message::*;
+use comms::{send, recv};
+
+/// Returns `(remaining_ms, active)` for the watchdog identified by `id`.
+#[no_mangle]
+pub extern fn watchdog_query(id: usize) -> (u64, bool) {
+    send(&WatchdogQueryRequest { id });
+    recv(|reply| match *reply {
+        WatchdogQueryReply { remaining_ms, active } => (remaining_ms, active),
+        _ => panic!("unexpected reply to WatchdogQueryRequest")
+    })
+}
@@ -15,7 +15,31 @@ pub struct Exception<'a> {
     pub column:   u32,
     pub function: &'a str,
     pub message:  &'a str,
-    pub param:    [i64; 3]
+    pub param:    [i64; 3],
+    pub cause:    Option<&'a Exception<'a>>
+}
+
+impl<'a> Exception<'a> {
+    /// Iterates this exception followed by each underlying `cause`,
+    /// outermost first, so a fault can be reported as "X, caused by Y,
+    /// caused by Z" instead of only showing the outermost frame.
+    pub fn chain<'r>(&'r self) -> ExceptionChain<'r, 'a> {
+        ExceptionChain { next: Some(self) }
+    }
+}
+
+pub struct ExceptionChain<'r, 'a: 'r> {
+    next: Option<&'r Exception<'a>>
+}
+
+impl<'r, 'a: 'r> Iterator for ExceptionChain<'r, 'a> {
+    type Item = &'r Exception<'a>;
+
+    fn next(&mut self) -> Option<&'r Exception<'a>> {
+        let current = self.next.take();
+        self.next = current.and_then(|exception| exception.cause);
+        current
+    }
 }
 
 #[derive(Debug)]
@@ -48,6 +72,9 @@ pub enum Message<'a> {
     WatchdogSetRequest { ms: u64 },
     WatchdogSetReply   { id: usize },
     WatchdogClear      { id: usize },
+    WatchdogExpired    { id: usize },
+    WatchdogQueryRequest { id: usize },
+    WatchdogQueryReply   { remaining_ms: u64, active: bool },
 
     RpcSend {
         async: bool,
@@ -55,14 +82,30 @@ pub enum Message<'a> {
         tag: &'a [u8],
         data: *const *const ()
     },
+    RpcSendReply { id: u32 },
     RpcRecvRequest(*mut ()),
     RpcRecvReply(Result<usize, Exception<'a>>),
+    RpcCancelRequest { id: u32 },
+    RpcAsyncDrainRequest,
+    RpcAsyncDrainReply { pending: u32 },
 
     CacheGetRequest { key: &'a str },
     CacheGetReply   { value: &'static [i32] },
     CachePutRequest { key: &'a str, value: &'a [i32] },
     CachePutReply   { succeeded: bool },
 
+    CacheGetBytesRequest { key: &'a str },
+    CacheGetBytesReply   { value: &'static [u8] },
+    CachePutBytesRequest { key: &'a str, value: &'a [u8] },
+    CachePutBytesReply   { succeeded: bool },
+
+    DmaRecordStart(&'a str),
+    DmaRecordAppend(&'a [u8]),
+    DmaRecordStop { duration: u64 },
+    DmaEraseRequest { name: &'a str },
+    DmaRetrieveRequest { name: &'a str },
+    DmaRetrieveReply { trace: Option<&'static [u8]>, duration: u64 },
+
     I2cStartRequest { busno: u8 },
     I2cStopRequest { busno: u8 },
     I2cWriteRequest { busno: u8, data: u8 },
@@ -70,6 +113,13 @@ pub enum Message<'a> {
     I2cReadRequest { busno: u8, ack: bool },
     I2cReadReply { data: u8 },
 
+    SpiSetConfigRequest { busno: u8, flags: u8, length: u8, div: u8, cs: u8 },
+    SpiSetConfigReply { succeeded: bool },
+    SpiWriteRequest { busno: u8, data: u32 },
+    SpiWriteReply { succeeded: bool },
+    SpiReadRequest { busno: u8 },
+    SpiReadReply { data: u32 },
+
     Log(fmt::Arguments<'a>),
     LogSlice(&'a str)
 }
@@ -0,0 +1,227 @@
+//! Comms-CPU side handling for kernel-originated messages that need
+//! state kept across calls (SPI, DMA, typed cache, watchdog and async
+//! RPC bookkeeping), plus chaining a fault's underlying cause onto it
+//! before relaying it to the kernel. `KernelSession::handle` slots into
+//! the same dispatch loop that already answers `I2c*`/`CacheGetRequest`,
+//! and owns the state those new message groups need between one request
+//! and the next.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+use kernel_proto::{Exception, Message};
+
+/// Software model of one SPI bus's configuration and last transferred
+/// word; swapped for the board's SPI controller once this is wired into
+/// the real session loop.
+#[derive(Default)]
+struct SpiBus {
+    flags:  u8,
+    length: u8,
+    div:    u8,
+    cs:     u8,
+    last:   u32,
+}
+
+/// One recorded DMA trace: the concatenated raw RTIO words, leaked once
+/// when recording stops, and the playback duration. `data` is leaked
+/// (not cloned) here so repeated `DmaRetrieveRequest`s for the same
+/// trace — the whole point of replaying a recorded sequence — don't
+/// allocate on every call.
+#[derive(Default)]
+struct DmaTrace {
+    data:     &'static [u8],
+    duration: u64,
+}
+
+#[derive(Default)]
+pub struct KernelSession {
+    spi_buses:        BTreeMap<u8, SpiBus>,
+    dma_traces:       BTreeMap<String, DmaTrace>,
+    dma_recording:    Option<(String, Vec<u8>)>,
+    // id -> deadline, in milliseconds on the same clock as `tick`.
+    watchdogs:        BTreeMap<usize, u64>,
+    next_watchdog_id: usize,
+    now_ms:           u64,
+    // Leaked once at put time (see `CachePutBytesRequest`) so repeated
+    // gets don't allocate.
+    cache_bytes:      BTreeMap<String, &'static [u8]>,
+    rpc_pending:      BTreeSet<u32>,
+    next_rpc_id:      u32,
+    // Most recent RTIO fault seen while an RPC was outstanding, if any;
+    // attached as the `cause` of the next fault relayed to the kernel.
+    last_rtio_fault:  Option<Exception<'static>>,
+}
+
+impl KernelSession {
+    pub fn new() -> KernelSession {
+        KernelSession::default()
+    }
+
+    /// Answers one kernel-originated `Message`, returning the reply (if
+    /// any) to send back over the mailbox.
+    pub fn handle<'a>(&mut self, request: &Message<'a>) -> Option<Message<'a>> {
+        match *request {
+            Message::SpiSetConfigRequest { busno, flags, length, div, cs } => {
+                self.spi_buses.insert(busno, SpiBus { flags, length, div, cs, last: 0 });
+                Some(Message::SpiSetConfigReply { succeeded: true })
+            }
+            Message::SpiWriteRequest { busno, data } => {
+                match self.spi_buses.get_mut(&busno) {
+                    Some(bus) => {
+                        bus.last = data;
+                        Some(Message::SpiWriteReply { succeeded: true })
+                    }
+                    None => Some(Message::SpiWriteReply { succeeded: false })
+                }
+            }
+            Message::SpiReadRequest { busno } => {
+                let data = self.spi_buses.get(&busno).map_or(0, |bus| bus.last);
+                Some(Message::SpiReadReply { data })
+            }
+
+            Message::DmaRecordStart(name) => {
+                self.dma_recording = Some((String::from(name), Vec::new()));
+                None
+            }
+            Message::DmaRecordAppend(chunk) => {
+                // Appended chunks are concatenated in the order they
+                // arrive, so playback replays them back-to-back.
+                if let Some((_, ref mut buf)) = self.dma_recording {
+                    buf.extend_from_slice(chunk);
+                }
+                None
+            }
+            Message::DmaRecordStop { duration } => {
+                if let Some((name, data)) = self.dma_recording.take() {
+                    // Leaked once here, not on every retrieve: playback
+                    // can span kernel runs, and a kernel looping
+                    // retrieve->playback must not allocate each time.
+                    let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+                    self.dma_traces.insert(name, DmaTrace { data, duration });
+                }
+                None
+            }
+            Message::DmaEraseRequest { name } => {
+                self.dma_traces.remove(name);
+                None
+            }
+            Message::DmaRetrieveRequest { name } => {
+                match self.dma_traces.get(name) {
+                    Some(trace) => Some(Message::DmaRetrieveReply {
+                        trace: Some(trace.data),
+                        duration: trace.duration,
+                    }),
+                    None => Some(Message::DmaRetrieveReply { trace: None, duration: 0 })
+                }
+            }
+
+            Message::CacheGetBytesRequest { key } => {
+                let value = self.cache_bytes.get(key).cloned().unwrap_or(&[]);
+                Some(Message::CacheGetBytesReply { value })
+            }
+            Message::CachePutBytesRequest { key, value } => {
+                // Leaked once here, not on every get: a kernel reading
+                // a cached value in a loop — the whole point of a
+                // cache — must not allocate each time.
+                let value: &'static [u8] = Box::leak(value.to_vec().into_boxed_slice());
+                self.cache_bytes.insert(String::from(key), value);
+                Some(Message::CachePutBytesReply { succeeded: true })
+            }
+
+            Message::WatchdogSetRequest { ms } => {
+                let id = self.next_watchdog_id;
+                self.next_watchdog_id += 1;
+                self.watchdogs.insert(id, self.now_ms + ms);
+                Some(Message::WatchdogSetReply { id })
+            }
+            Message::WatchdogClear { id } => {
+                self.watchdogs.remove(&id);
+                None
+            }
+            Message::WatchdogQueryRequest { id } => {
+                match self.watchdogs.get(&id) {
+                    Some(&deadline) => {
+                        let remaining = deadline.saturating_sub(self.now_ms);
+                        Some(Message::WatchdogQueryReply { remaining_ms: remaining, active: remaining > 0 })
+                    }
+                    None => Some(Message::WatchdogQueryReply { remaining_ms: 0, active: false })
+                }
+            }
+
+            Message::RpcSend { async: true, .. } => {
+                let id = self.next_rpc_id;
+                self.next_rpc_id = self.next_rpc_id.wrapping_add(1);
+                self.rpc_pending.insert(id);
+                Some(Message::RpcSendReply { id })
+            }
+            Message::RpcCancelRequest { id } => {
+                self.rpc_pending.remove(&id);
+                None
+            }
+            Message::RpcAsyncDrainRequest => {
+                Some(Message::RpcAsyncDrainReply { pending: self.rpc_pending.len() as u32 })
+            }
+
+            _ => None
+        }
+    }
+
+    /// Records an RTIO fault observed while an RPC was outstanding, so
+    /// it can be chained onto the fault the RPC itself later reports.
+    pub fn record_rtio_fault(&mut self, fault: Exception<'static>) {
+        self.last_rtio_fault = Some(fault);
+    }
+
+    /// Attaches the most recently recorded RTIO fault (if any) as the
+    /// `cause` of `fault`, so the host sees "X, caused by Y" instead of
+    /// the RPC failure alone. Called from the existing RPC completion
+    /// path before relaying a failed call's exception to the kernel.
+    ///
+    /// Consumes the recorded fault: once it's been attached somewhere
+    /// it's cleared, so a later, unrelated exception doesn't get the
+    /// same stale cause glued onto it.
+    pub fn report_fault<'a>(&mut self, mut fault: Exception<'a>) -> Exception<'a> {
+        if let Some(cause) = self.last_rtio_fault.take() {
+            fault.cause = Some(Box::leak(Box::new(cause)));
+        }
+        fault
+    }
+
+    /// Renders a fault as "X, caused by Y, caused by Z" by walking its
+    /// `cause` chain, for the log line the comms CPU emits when it
+    /// can't relay the exception (e.g. the kernel has already gone
+    /// away).
+    pub fn describe_fault(fault: &Exception) -> String {
+        fault.chain()
+            .map(|exception| String::from(exception.message))
+            .collect::<Vec<_>>()
+            .join(", caused by ")
+    }
+
+    /// Marks a queued async RPC as completed, draining it from the
+    /// pending set reported to `RpcAsyncDrainRequest`. Called through
+    /// `rpc_queue::on_rpc_reply` once the host's reply for the call
+    /// arrives, so a kernel waiting to drain never sees a stale count.
+    pub fn rpc_completed(&mut self, id: u32) {
+        self.rpc_pending.remove(&id);
+    }
+
+    /// Advances the session's notion of time and returns a
+    /// `WatchdogExpired` for every watchdog whose deadline has passed,
+    /// clearing each so it is only ever reported once. Meant to be
+    /// called from the same loop that drives `handle`, on every tick of
+    /// the comms CPU's millisecond clock.
+    pub fn tick(&mut self, now_ms: u64) -> Vec<Message<'static>> {
+        self.now_ms = now_ms;
+        let expired: Vec<usize> = self.watchdogs.iter()
+            .filter(|&(_, &deadline)| deadline <= now_ms)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &expired {
+            self.watchdogs.remove(id);
+        }
+        expired.into_iter().map(|id| Message::WatchdogExpired { id }).collect()
+    }
+}
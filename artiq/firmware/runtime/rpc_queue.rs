@@ -0,0 +1,17 @@
+//! Resolves RPC calls against the host's control session reply, the
+//! call site `KernelSession::rpc_completed` needs so an async call that
+//! simply finishes — rather than being explicitly cancelled — is
+//! actually drained from the pending set `RpcAsyncDrainRequest` reports
+//! on. Without this, draining before `RunFinished` would spin forever
+//! on any async RPC that completed normally.
+
+use kernel_session::KernelSession;
+
+/// Called once the host's reply for RPC call `id` arrives over the
+/// control session, whether the call was sent synchronously or
+/// asynchronously. Wire this into the session's host-reply read loop,
+/// right where a synchronous call's result is matched up with the
+/// kernel's pending `RpcRecvRequest`.
+pub fn on_rpc_reply(session: &mut KernelSession, id: u32) {
+    session.rpc_completed(id);
+}